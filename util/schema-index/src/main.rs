@@ -1,16 +1,135 @@
 use anyhow::anyhow;
-use git2::{Repository, Sort, TreeWalkResult};
+use futures::stream::{self, StreamExt};
+use git2::{Oid, Repository, Sort, TreeWalkResult};
 use globset::Glob;
 use hex::ToHex;
 use path_clean::PathClean;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::{collections::HashSet, ffi::OsStr, path::PathBuf};
+use std::{
+    collections::{HashMap, HashSet},
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 use structopt::StructOpt;
-use taplo::schema::{SchemaExtraInfo, SchemaIndex, SchemaMeta};
 use time::{Format, OffsetDateTime};
 use walkdir::WalkDir;
 
+/// A schema that failed meta-schema validation, reported at the end of the run.
+struct ValidationFailure {
+    path: String,
+    message: String,
+}
+
+/// `taplo::schema::SchemaExtraInfo` doesn't (yet) expose `dialect`/`id`, and
+/// `taplo::schema::SchemaMeta` doesn't (yet) expose `content_hash`; defined
+/// locally rather than waiting on an upstream release, so this tool can record
+/// declared dialects and content hashes today. Field-for-field
+/// compatible with the upstream shape otherwise.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+struct SchemaExtraInfo {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    authors: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dialect: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    patterns: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+struct SchemaMeta {
+    title: Option<String>,
+    description: Option<String>,
+    updated: Option<String>,
+    url: String,
+    url_hash: String,
+    content_hash: String,
+    extra: SchemaExtraInfo,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+struct SchemaIndex {
+    #[serde(default)]
+    schemas: Vec<SchemaMeta>,
+}
+
+/// Parse `bytes` as a JSON Schema document and validate it against the meta-schema
+/// for its declared dialect (detected from `$schema`, falling back to
+/// `fallback_dialect` when absent), returning the parsed document on success,
+/// with `fallback_dialect` filled in as its `$schema` where it had none.
+fn check_schema(bytes: &[u8], fallback_dialect: &str) -> Result<serde_json::Value, String> {
+    let mut value: serde_json::Value =
+        serde_json::from_slice(bytes).map_err(|err| err.to_string())?;
+
+    if let Some(obj) = value.as_object_mut() {
+        obj.entry("$schema")
+            .or_insert_with(|| serde_json::Value::String(fallback_dialect.to_owned()));
+    }
+
+    jsonschema::meta::validate(&value)
+        .map_err(|err| format!("{} (at {})", err, err.instance_path))?;
+
+    Ok(value)
+}
+
+/// Parse an HTTP-date (RFC 7231, e.g. `Wed, 21 Oct 2015 07:28:00 GMT`). This is
+/// RFC 2822 with a fixed `GMT` zone instead of a numeric offset, which `time`
+/// 0.2's `Format::Rfc2822` doesn't accept, so swap the token for `+0000` first.
+fn parse_http_date(value: &str) -> Option<OffsetDateTime> {
+    let value = value
+        .strip_suffix("GMT")
+        .map(|prefix| format!("{}+0000", prefix))
+        .unwrap_or_else(|| value.to_owned());
+
+    OffsetDateTime::parse(&value, Format::Rfc2822).ok()
+}
+
+/// Invalid or unparseable schemas are recorded in `failures` and excluded from the
+/// index unless `--strict` aborts the run instead.
+fn validate_schema(
+    path: &str,
+    bytes: &[u8],
+    fallback_dialect: &str,
+    failures: &mut Vec<ValidationFailure>,
+) -> bool {
+    match check_schema(bytes, fallback_dialect) {
+        Ok(_) => true,
+        Err(message) => {
+            failures.push(ValidationFailure {
+                path: path.to_owned(),
+                message,
+            });
+            false
+        }
+    }
+}
+
+/// Everything about a schema that is expensive to (re)compute from history,
+/// cached under the blob OID that produced it.
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    updated: Option<String>,
+    content_hash: String,
+    title: Option<String>,
+    description: Option<String>,
+    extra: SchemaExtraInfo,
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct SchemaStoreSchema {
@@ -37,6 +156,115 @@ struct SchemaWithExtraInfo {
     extra: SchemaExtraInfo,
 }
 
+/// A single, parsed `--source` locator.
+///
+/// Each variant is a distinct way of harvesting schemas; keeping the parsing
+/// and the enforcement per-variant means adding a new kind of source doesn't
+/// touch the others.
+#[derive(Debug, Clone)]
+enum SchemaSource {
+    /// `file://` a local directory of schemas, or a schemastore-style catalog file.
+    File(PathBuf),
+    /// `git+https://...#ref` a remote git repository, optionally pinned to a ref.
+    Git {
+        url: String,
+        reference: Option<String>,
+    },
+    /// `tarball+https://...` an archive of schemas to download and extract.
+    Tarball(String),
+    /// `schemastore` the schemastore.org JSON catalog.
+    SchemaStore,
+}
+
+impl FromStr for SchemaSource {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "schemastore" {
+            return Ok(SchemaSource::SchemaStore);
+        }
+
+        if let Some(rest) = s.strip_prefix("file://") {
+            return Ok(SchemaSource::File(PathBuf::from(rest)));
+        }
+
+        if let Some(rest) = s.strip_prefix("git+") {
+            return Ok(match rest.rsplit_once('#') {
+                Some((url, reference)) => SchemaSource::Git {
+                    url: url.to_string(),
+                    reference: Some(reference.to_string()),
+                },
+                None => SchemaSource::Git {
+                    url: rest.to_string(),
+                    reference: None,
+                },
+            });
+        }
+
+        if let Some(rest) = s.strip_prefix("tarball+") {
+            return Ok(SchemaSource::Tarball(rest.to_string()));
+        }
+
+        Err(anyhow!(
+            "unrecognized --source URI `{}`, expected one of file://, git+, tarball+ or schemastore",
+            s
+        ))
+    }
+}
+
+impl SchemaSource {
+    /// Harvest the schemas this source describes into `SchemaMeta`s.
+    fn load(
+        &self,
+        opt: &Opt,
+        failures: &mut Vec<ValidationFailure>,
+    ) -> anyhow::Result<Vec<SchemaMeta>> {
+        match self {
+            SchemaSource::File(path) => {
+                if path.is_file() {
+                    index_catalog_file(path, &opt.fallback_dialect, failures)
+                } else {
+                    index_local_dir(path, &opt.url, &opt.fallback_dialect, failures)
+                }
+            }
+            SchemaSource::Git { url, reference } => {
+                let dir = tempfile::tempdir()?;
+
+                let mut fo = git2::FetchOptions::new();
+                fo.download_tags(git2::AutotagOption::None);
+
+                let repo = git2::build::RepoBuilder::new()
+                    .fetch_options(fo)
+                    .clone(url, dir.path())?;
+
+                if let Some(reference) = reference {
+                    let (object, _) = repo.revparse_ext(reference)?;
+                    repo.checkout_tree(&object, None)?;
+                    repo.set_head_detached(object.id())?;
+                }
+
+                index_git_dir(
+                    &repo,
+                    dir.path(),
+                    Path::new(""),
+                    &opt.url,
+                    None,
+                    &opt.fallback_dialect,
+                    failures,
+                )
+            }
+            SchemaSource::Tarball(url) => {
+                let bytes = reqwest::blocking::get(url)?.bytes()?;
+                let dir = tempfile::tempdir()?;
+                let decoder = flate2::read::GzDecoder::new(&bytes[..]);
+                tar::Archive::new(decoder).unpack(dir.path())?;
+                index_local_dir(dir.path(), &opt.url, &opt.fallback_dialect, failures)
+            }
+            SchemaSource::SchemaStore => fetch_schema_store(&opt.fallback_dialect, failures),
+        }
+    }
+}
+
 /// A basic example
 #[derive(StructOpt, Debug)]
 #[structopt(name = "basic")]
@@ -57,23 +285,306 @@ struct Opt {
     #[structopt(name = "DIR")]
     dir: PathBuf,
 
-    /// Use schemastore.org for additional toml-compatible schemas.
+    /// Additional schema catalog to merge in, e.g. `schemastore`,
+    /// `file:///path/to/schemas`, `git+https://github.com/foo/bar#main` or
+    /// `tarball+https://example.com/schemas.tar.gz`. May be repeated.
+    #[structopt(long = "source")]
+    sources: Vec<SchemaSource>,
+
+    /// Path to a sled database used to cache computed schema metadata by blob OID,
+    /// so unchanged files skip the history revwalk on subsequent runs.
+    #[structopt(long, default_value = "schema_index.cache")]
+    cache: PathBuf,
+
+    /// Disable the on-disk cache and always recompute every schema from history.
+    #[structopt(long)]
+    no_cache: bool,
+
+    /// Meta-schema dialect to assume for a schema that has no `$schema` field.
+    #[structopt(long, default_value = "http://json-schema.org/draft-07/schema#")]
+    fallback_dialect: String,
+
+    /// Abort instead of excluding schemas that fail meta-schema validation.
+    #[structopt(long)]
+    strict: bool,
+
+    /// Output format(s) to emit. May be repeated or given as a comma-separated list.
+    /// `rkyv` requires building this tool with `--features rkyv`.
+    #[structopt(long, default_value = "json", use_delimiter = true)]
+    format: Vec<OutputFormat>,
+
+    /// Output path for the `rkyv` archive. Defaults to `out` with its extension
+    /// replaced by `.rkyv`.
+    #[structopt(long)]
+    rkyv_out: Option<PathBuf>,
+
+    /// Path to a raw 32-byte ed25519 key: the signing key when generating an
+    /// index, or the corresponding public key when used with `--verify`.
     #[structopt(long)]
-    schema_store: bool,
+    sign_key: Option<PathBuf>,
+
+    /// Instead of generating an index, re-hash the file at `out` and check it
+    /// against a manifest previously written by `--sign-key`.
+    #[structopt(long)]
+    verify: Option<PathBuf>,
+}
+
+/// The sidecar written next to a signed index, recording what was signed and by what.
+#[derive(Serialize, Deserialize)]
+struct SignedManifest {
+    index_hash: String,
+    signature: String,
+    generator_version: String,
+    generated: String,
+}
+
+fn manifest_path_for(out: &str) -> PathBuf {
+    PathBuf::from(format!("{}.manifest.json", out))
+}
+
+/// Sign `index_bytes` (the exact bytes written to `out`) with the ed25519 key at
+/// `key_path`, producing a manifest that a client can later check with `--verify`.
+fn sign_index(index_bytes: &[u8], key_path: &Path) -> anyhow::Result<SignedManifest> {
+    use ed25519_dalek::Signer;
+
+    let key_bytes = std::fs::read(key_path)?;
+    let seed: [u8; 32] = key_bytes
+        .get(..32)
+        .ok_or_else(|| anyhow!("signing key at {:?} must be at least 32 bytes", key_path))?
+        .try_into()
+        .unwrap();
+    let signing_key = ed25519_dalek::SigningKey::from_bytes(&seed);
+
+    let mut hasher = Sha256::new();
+    hasher.update(index_bytes);
+    let index_hash = hasher.finalize().encode_hex::<String>();
+
+    let signature = signing_key.sign(index_bytes);
+
+    Ok(SignedManifest {
+        index_hash,
+        signature: signature.to_bytes().encode_hex::<String>(),
+        generator_version: env!("CARGO_PKG_VERSION").to_string(),
+        generated: OffsetDateTime::now_utc().format(Format::Rfc3339),
+    })
+}
+
+/// Re-hash the index at `out` and check the result against a previously written
+/// manifest, verifying the detached signature with the public key at `key_path`.
+fn verify_index(out: &str, manifest_path: &Path, key_path: &Path) -> anyhow::Result<()> {
+    use ed25519_dalek::Verifier;
+
+    let manifest: SignedManifest = serde_json::from_reader(std::fs::File::open(manifest_path)?)?;
+
+    let index_bytes = std::fs::read(out)?;
+    let mut hasher = Sha256::new();
+    hasher.update(&index_bytes);
+    let index_hash = hasher.finalize().encode_hex::<String>();
+
+    if index_hash != manifest.index_hash {
+        return Err(anyhow!(
+            "index hash mismatch: manifest says {}, {} hashes to {}",
+            manifest.index_hash,
+            out,
+            index_hash
+        ));
+    }
+
+    let key_bytes = std::fs::read(key_path)?;
+    let key: [u8; 32] = key_bytes
+        .get(..32)
+        .ok_or_else(|| anyhow!("public key at {:?} must be at least 32 bytes", key_path))?
+        .try_into()
+        .unwrap();
+    let verifying_key = ed25519_dalek::VerifyingKey::from_bytes(&key)?;
+
+    let sig_bytes = hex::decode(&manifest.signature)?;
+    let sig: [u8; 64] = sig_bytes
+        .get(..64)
+        .ok_or_else(|| anyhow!("manifest signature is not 64 bytes"))?
+        .try_into()
+        .unwrap();
+    let signature = ed25519_dalek::Signature::from_bytes(&sig);
+
+    verifying_key
+        .verify(&index_bytes, &signature)
+        .map_err(|err| anyhow!("signature verification failed: {}", err))?;
+
+    println!(
+        "{} matches {} and is signed correctly",
+        out,
+        manifest_path.display()
+    );
+
+    Ok(())
+}
+
+/// An on-disk representation the index can be emitted as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    Json,
+    /// A memory-mappable `rkyv` archive, for near-zero-cost lookups by clients.
+    Rkyv,
+}
+
+impl FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(OutputFormat::Json),
+            "rkyv" => Ok(OutputFormat::Rkyv),
+            other => Err(anyhow!(
+                "unrecognized --format `{}`, expected json or rkyv",
+                other
+            )),
+        }
+    }
 }
 
 fn main() -> anyhow::Result<()> {
     let mut opt = Opt::from_args();
 
+    if let Some(manifest_path) = &opt.verify {
+        let key_path = opt
+            .sign_key
+            .as_ref()
+            .ok_or_else(|| anyhow!("--verify requires --sign-key pointing at the public key"))?;
+        return verify_index(&opt.out, manifest_path, key_path);
+    }
+
+    if opt.sign_key.is_some() && !opt.format.contains(&OutputFormat::Json) {
+        return Err(anyhow!(
+            "--sign-key signs the JSON artifact at --out, which requires `--format json` \
+             (or the default) to be written"
+        ));
+    }
+
     opt.url = opt.url.trim_end_matches('/').into();
 
     let repo = Repository::discover(&opt.git)?;
 
+    let cache = if opt.no_cache {
+        None
+    } else {
+        Some(sled::open(&opt.cache)?)
+    };
+
+    let mut index = SchemaIndex::default();
+    let mut seen_url_hashes: HashSet<String> = HashSet::new();
+    let mut failures: Vec<ValidationFailure> = Vec::new();
+
+    for meta in index_git_dir(
+        &repo,
+        &opt.git,
+        &opt.dir,
+        &opt.url,
+        cache.as_ref(),
+        &opt.fallback_dialect,
+        &mut failures,
+    )? {
+        if seen_url_hashes.insert(meta.url_hash.clone()) {
+            index.schemas.push(meta);
+        }
+    }
+
+    for source in &opt.sources {
+        match source.load(&opt, &mut failures) {
+            Ok(metas) => {
+                for meta in metas {
+                    if seen_url_hashes.insert(meta.url_hash.clone()) {
+                        index.schemas.push(meta);
+                    }
+                }
+            }
+            Err(err) => {
+                println!("error loading source {:?}: {}", source, err);
+            }
+        }
+    }
+
+    if !failures.is_empty() {
+        println!("{} schema(s) failed validation:", failures.len());
+        for failure in &failures {
+            println!("  {}: {}", failure.path, failure.message);
+        }
+
+        if opt.strict {
+            return Err(anyhow!(
+                "{} schema(s) failed meta-schema validation",
+                failures.len()
+            ));
+        }
+    }
+
+    let json_bytes = serde_json::to_vec(&index)?;
+
+    if opt.format.contains(&OutputFormat::Json) {
+        std::fs::write(&opt.out, &json_bytes)?;
+    }
+
+    if opt.format.contains(&OutputFormat::Rkyv) {
+        let rkyv_out = opt
+            .rkyv_out
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(&opt.out).with_extension("rkyv"));
+        write_rkyv_index(&index, &rkyv_out)?;
+    }
+
+    if let Some(key_path) = &opt.sign_key {
+        let manifest = sign_index(&json_bytes, key_path)?;
+        serde_json::to_writer_pretty(
+            std::fs::File::create(manifest_path_for(&opt.out))?,
+            &manifest,
+        )?;
+    }
+
+    if let Some(cache) = cache {
+        cache.flush()?;
+    }
+
+    Ok(())
+}
+
+/// Archive `index` in `rkyv`'s zero-copy format so a client can memory-map the
+/// file and look up a schema by `url_hash` without deserializing the rest.
+/// Requires `SchemaIndex`, `SchemaMeta` and `SchemaExtraInfo` to derive
+/// `rkyv::Archive`/`Serialize`/`Deserialize` behind the same feature.
+#[cfg(feature = "rkyv")]
+fn write_rkyv_index(index: &SchemaIndex, path: &Path) -> anyhow::Result<()> {
+    let bytes = rkyv::to_bytes::<_, 4096>(index)
+        .map_err(|err| anyhow!("failed to archive schema index: {}", err))?;
+    std::fs::write(path, &bytes)?;
+    Ok(())
+}
+
+#[cfg(not(feature = "rkyv"))]
+fn write_rkyv_index(_index: &SchemaIndex, _path: &Path) -> anyhow::Result<()> {
+    Err(anyhow!(
+        "--format rkyv requires building schema-index with `--features rkyv`"
+    ))
+}
+
+/// Walk `dir` (relative to `git_root`) across the history of `repo`, newest commit
+/// first, assigning each schema's `updated` timestamp from the first commit whose
+/// tree contains its path.
+/// `cache`, when given, lets unchanged blobs skip the revwalk entirely.
+fn index_git_dir(
+    repo: &Repository,
+    git_root: &Path,
+    dir: &Path,
+    base_url: &str,
+    cache: Option<&sled::Db>,
+    fallback_dialect: &str,
+    failures: &mut Vec<ValidationFailure>,
+) -> anyhow::Result<Vec<SchemaMeta>> {
+    let mut out = Vec::new();
+
     let mut revs = repo.revwalk().unwrap();
     revs.push_head().unwrap();
     revs.set_sorting(Sort::TIME).unwrap();
 
-    let mut files = WalkDir::new(opt.git.join(&opt.dir))
+    let mut files = WalkDir::new(git_root.join(dir))
         .into_iter()
         .filter_map(|res| {
             res.ok().map(|entry| entry.into_path()).and_then(|p| {
@@ -86,7 +597,48 @@ fn main() -> anyhow::Result<()> {
         })
         .collect::<HashSet<_>>();
 
-    let mut index = SchemaIndex::default();
+    // Resolve the current blob OID of every candidate file from the HEAD tree so
+    // cache hits can skip the revwalk below entirely.
+    let mut blob_oids: HashMap<PathBuf, Oid> = HashMap::new();
+    let head_tree = repo.head()?.peel_to_tree()?;
+    head_tree.walk(git2::TreeWalkMode::PostOrder, |tree_dir, entry| {
+        if let Some(name) = entry.name() {
+            let fpath = git_root.join(tree_dir).join(name).clean();
+            if files.contains(&fpath) {
+                blob_oids.insert(fpath, entry.id());
+            }
+        }
+        TreeWalkResult::Ok
+    })?;
+
+    if let Some(cache) = cache {
+        for fpath in blob_oids.keys().cloned().collect::<Vec<_>>() {
+            let oid = blob_oids[&fpath];
+
+            if let Some(raw) = cache.get(oid.as_bytes())? {
+                let entry: CacheEntry = serde_json::from_slice(&raw)?;
+
+                let name = fpath.file_name().and_then(OsStr::to_str).unwrap();
+                let url = format!("{}/{}", base_url, name);
+
+                let mut hasher = Sha256::new();
+                hasher.update(url.as_bytes());
+                let url_hash = hasher.finalize().encode_hex::<String>();
+
+                out.push(SchemaMeta {
+                    title: entry.title,
+                    description: entry.description,
+                    updated: entry.updated,
+                    url,
+                    url_hash,
+                    content_hash: entry.content_hash,
+                    extra: entry.extra,
+                });
+
+                files.remove(&fpath);
+            }
+        }
+    }
 
     for result in revs {
         let rev = match result {
@@ -102,36 +654,73 @@ fn main() -> anyhow::Result<()> {
             commit
                 .tree()
                 .unwrap()
-                .walk(git2::TreeWalkMode::PostOrder, |dir, entry| {
+                .walk(git2::TreeWalkMode::PostOrder, |tree_dir, entry| {
                     if let Some(name) = entry.name() {
-                        let fpath = opt.git.join(dir).join(name).clean();
+                        let fpath = git_root.join(tree_dir).join(name).clean();
                         if files.remove(&fpath) {
-                            let s: SchemaWithExtraInfo =
-                                match serde_json::from_reader(std::fs::File::open(&fpath).unwrap())
-                                {
-                                    Ok(s) => s,
-                                    Err(err) => {
-                                        panic!("invalid schema: {:?}: {}", fpath, err);
-                                    }
+                            let bytes = std::fs::read(&fpath).unwrap();
+
+                            let path_str = fpath.display().to_string();
+
+                            let parsed =
+                                if validate_schema(&path_str, &bytes, fallback_dialect, failures) {
+                                    // A valid JSON Schema document (e.g. a boolean schema, or one
+                                    // with a malformed `x-taplo-info`) need not deserialize into
+                                    // `SchemaWithExtraInfo`;
+                                    // treat that as exclusion rather than a panic.
+                                    serde_json::from_slice::<SchemaWithExtraInfo>(&bytes)
+                                        .map_err(|err| {
+                                            failures.push(ValidationFailure {
+                                                path: path_str.clone(),
+                                                message: err.to_string(),
+                                            });
+                                        })
+                                        .ok()
+                                } else {
+                                    None
                                 };
 
-                            let url = format!("{}/{}", &opt.url, name);
+                            if let Some(s) = parsed {
+                                let url = format!("{}/{}", base_url, name);
 
-                            let mut hasher = Sha256::new();
-                            hasher.update(url.as_bytes());
-                            let url_hash = hasher.finalize().encode_hex::<String>();
+                                let mut hasher = Sha256::new();
+                                hasher.update(url.as_bytes());
+                                let url_hash = hasher.finalize().encode_hex::<String>();
 
-                            index.schemas.push(SchemaMeta {
-                                title: s.title,
-                                description: s.description,
-                                updated: Some(
+                                let mut content_hasher = Sha256::new();
+                                content_hasher.update(&bytes);
+                                let content_hash = content_hasher.finalize().encode_hex::<String>();
+
+                                let updated = Some(
                                     OffsetDateTime::from_unix_timestamp(time_unix)
                                         .format(Format::Rfc3339),
-                                ),
-                                url,
-                                url_hash,
-                                extra: s.extra,
-                            })
+                                );
+
+                                if let Some(cache) = cache {
+                                    if let Some(oid) = blob_oids.get(&fpath) {
+                                        let entry = CacheEntry {
+                                            updated: updated.clone(),
+                                            content_hash: content_hash.clone(),
+                                            title: s.title.clone(),
+                                            description: s.description.clone(),
+                                            extra: s.extra.clone(),
+                                        };
+                                        if let Ok(raw) = serde_json::to_vec(&entry) {
+                                            let _ = cache.insert(oid.as_bytes(), raw);
+                                        }
+                                    }
+                                }
+
+                                out.push(SchemaMeta {
+                                    title: s.title,
+                                    description: s.description,
+                                    updated,
+                                    url,
+                                    url_hash,
+                                    content_hash,
+                                    extra: s.extra,
+                                })
+                            }
                         }
                     }
 
@@ -148,25 +737,105 @@ fn main() -> anyhow::Result<()> {
         return Err(anyhow!("all files must be committed"));
     }
 
-    if opt.schema_store {
-        if let Err(err) = fetch_schema_store(&mut index) {
-            println!("error fetching schema store: {}", err);
+    Ok(out)
+}
+
+/// Index a plain directory of schemas that isn't (necessarily) version-controlled,
+/// using the file's mtime as its `updated` timestamp.
+fn index_local_dir(
+    dir: &Path,
+    base_url: &str,
+    fallback_dialect: &str,
+    failures: &mut Vec<ValidationFailure>,
+) -> anyhow::Result<Vec<SchemaMeta>> {
+    let mut out = Vec::new();
+
+    for entry in WalkDir::new(dir).into_iter().filter_map(|res| res.ok()) {
+        let fpath = entry.path();
+        if fpath.extension() != Some(OsStr::new("json")) {
+            continue;
         }
-    }
 
-    serde_json::to_writer(std::fs::File::create(opt.out).unwrap(), &index)?;
+        let bytes = std::fs::read(fpath)?;
 
-    Ok(())
+        if !validate_schema(
+            &fpath.display().to_string(),
+            &bytes,
+            fallback_dialect,
+            failures,
+        ) {
+            continue;
+        }
+
+        // A valid JSON Schema document (e.g. a boolean schema, or one with a malformed
+        // `x-taplo-info`) need not deserialize into `SchemaWithExtraInfo`; treat that as
+        // exclusion rather than a panic.
+        let s: SchemaWithExtraInfo = match serde_json::from_slice(&bytes) {
+            Ok(s) => s,
+            Err(err) => {
+                failures.push(ValidationFailure {
+                    path: fpath.display().to_string(),
+                    message: err.to_string(),
+                });
+                continue;
+            }
+        };
+
+        let name = fpath.file_name().and_then(OsStr::to_str).unwrap();
+        let url = format!("{}/{}", base_url, name);
+
+        let mut hasher = Sha256::new();
+        hasher.update(url.as_bytes());
+        let url_hash = hasher.finalize().encode_hex::<String>();
+
+        let mut content_hasher = Sha256::new();
+        content_hasher.update(&bytes);
+        let content_hash = content_hasher.finalize().encode_hex::<String>();
+
+        let updated = entry
+            .metadata()
+            .ok()
+            .and_then(|m| m.modified().ok())
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|secs| {
+                OffsetDateTime::from_unix_timestamp(secs.as_secs() as i64).format(Format::Rfc3339)
+            });
+
+        out.push(SchemaMeta {
+            title: s.title,
+            description: s.description,
+            updated,
+            url,
+            url_hash,
+            content_hash,
+            extra: s.extra,
+        });
+    }
+
+    Ok(out)
 }
 
-fn fetch_schema_store(index: &mut SchemaIndex) -> Result<(), anyhow::Error> {
-    let catalog: SchemaStoreCatalog =
-        reqwest::blocking::get("https://www.schemastore.org/api/json/catalog.json")?.json()?;
+/// Harvest a schemastore-style catalog file: each entry's `url` is fetched over
+/// HTTP(S) if it's a remote URL, or read relative to the catalog's own directory
+/// otherwise.
+fn index_catalog_file(
+    path: &Path,
+    fallback_dialect: &str,
+    failures: &mut Vec<ValidationFailure>,
+) -> anyhow::Result<Vec<SchemaMeta>> {
+    let catalog: SchemaStoreCatalog = serde_json::from_slice(&std::fs::read(path)?)?;
+    let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
 
-    let now_ts = OffsetDateTime::now_utc().format(Format::Rfc3339);
+    let mut out = Vec::new();
 
     for schema in catalog.schemas {
-        if !schema.file_match.iter().any(|m| m.ends_with(".toml")) {
+        let bytes = if schema.url.starts_with("http://") || schema.url.starts_with("https://") {
+            reqwest::blocking::get(&schema.url)?.bytes()?.to_vec()
+        } else {
+            std::fs::read(base_dir.join(&schema.url))?
+        };
+
+        if !validate_schema(&schema.url, &bytes, fallback_dialect, failures) {
             continue;
         }
 
@@ -174,28 +843,28 @@ fn fetch_schema_store(index: &mut SchemaIndex) -> Result<(), anyhow::Error> {
         hasher.update(schema.url.as_bytes());
         let url_hash = hasher.finalize().encode_hex::<String>();
 
+        let mut content_hasher = Sha256::new();
+        content_hasher.update(&bytes);
+        let content_hash = content_hasher.finalize().encode_hex::<String>();
+
+        // `fileMatch` globs are schemastore's format; taplo wants regexes, same
+        // conversion as the schemastore.org catalog path below.
         let mut globs: Vec<Glob> = Vec::new();
 
         for fm in schema.file_match.iter().filter(|s| s.ends_with(".toml")) {
-            match Glob::new(fm.trim_end_matches(".toml")) {
-                Ok(glob) => {
-                    globs.push(glob);
-                }
-                Err(_) => {
-                    continue;
-                }
-            };
+            if let Ok(glob) = Glob::new(fm.trim_end_matches(".toml")) {
+                globs.push(glob);
+            }
         }
 
-        let sm = SchemaMeta {
+        out.push(SchemaMeta {
             title: schema.name,
             description: schema.description,
-            // We don't know.
-            updated: Some(now_ts.clone()),
+            updated: None,
             url: schema.url,
             url_hash,
+            content_hash,
             extra: SchemaExtraInfo {
-                authors: vec!["automatically included from https://schemastore.org".into()],
                 patterns: globs
                     .into_iter()
                     .map(|g| {
@@ -217,10 +886,190 @@ fn fetch_schema_store(index: &mut SchemaIndex) -> Result<(), anyhow::Error> {
                     .collect(),
                 ..Default::default()
             },
-        };
+        });
+    }
 
-        index.schemas.push(sm);
+    Ok(out)
+}
+
+/// How many catalog bodies to fetch concurrently.
+const SCHEMA_STORE_CONCURRENCY: usize = 16;
+
+enum SchemaStoreOutcome {
+    Meta(SchemaMeta),
+    Invalid(ValidationFailure),
+}
+
+/// Blocking entry point: spins up a short-lived runtime to drive the bounded,
+/// concurrent fetch below, then folds the outcomes back into the caller's
+/// sequential `failures` accumulator.
+fn fetch_schema_store(
+    fallback_dialect: &str,
+    failures: &mut Vec<ValidationFailure>,
+) -> anyhow::Result<Vec<SchemaMeta>> {
+    let rt = tokio::runtime::Runtime::new()?;
+
+    let outcomes = rt.block_on(fetch_schema_store_async(fallback_dialect))?;
+
+    let mut out = Vec::new();
+    for outcome in outcomes {
+        match outcome {
+            SchemaStoreOutcome::Meta(meta) => out.push(meta),
+            SchemaStoreOutcome::Invalid(failure) => failures.push(failure),
+        }
     }
 
-    Ok(())
+    // `buffer_unordered` above completes in network-completion order, not catalog
+    // order; sort by URL so these entries keep the same order across runs
+    // against the same catalog.
+    out.sort_by(|a, b| a.url.cmp(&b.url));
+
+    Ok(out)
+}
+
+async fn fetch_schema_store_async(
+    fallback_dialect: &str,
+) -> anyhow::Result<Vec<SchemaStoreOutcome>> {
+    let client = reqwest::Client::new();
+
+    let catalog: SchemaStoreCatalog = client
+        .get("https://www.schemastore.org/api/json/catalog.json")
+        .send()
+        .await?
+        .json()
+        .await?;
+
+    let outcomes = stream::iter(
+        catalog
+            .schemas
+            .into_iter()
+            .filter(|schema| schema.file_match.iter().any(|m| m.ends_with(".toml"))),
+    )
+    .map(|schema| {
+        let client = client.clone();
+        async move { fetch_schema_store_entry(&client, schema, fallback_dialect).await }
+    })
+    .buffer_unordered(SCHEMA_STORE_CONCURRENCY)
+    .collect::<Vec<_>>()
+    .await;
+
+    Ok(outcomes)
+}
+
+async fn fetch_schema_store_entry(
+    client: &reqwest::Client,
+    schema: SchemaStoreSchema,
+    fallback_dialect: &str,
+) -> SchemaStoreOutcome {
+    let mut hasher = Sha256::new();
+    hasher.update(schema.url.as_bytes());
+    let url_hash = hasher.finalize().encode_hex::<String>();
+
+    let response = match client.get(&schema.url).send().await {
+        Ok(response) => response,
+        Err(err) => {
+            return SchemaStoreOutcome::Invalid(ValidationFailure {
+                path: schema.url,
+                message: err.to_string(),
+            })
+        }
+    };
+
+    // The catalog has no real timestamps of its own; fall back to the transport's
+    // notion of freshness when the server provides one. `ETag` is an opacity
+    // token, not a date, so it's not usable here even when present.
+    let updated_from_headers = response
+        .headers()
+        .get(reqwest::header::LAST_MODIFIED)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_http_date)
+        .map(|dt| dt.format(Format::Rfc3339));
+
+    let body = match response.bytes().await {
+        Ok(body) => body,
+        Err(err) => {
+            return SchemaStoreOutcome::Invalid(ValidationFailure {
+                path: schema.url,
+                message: err.to_string(),
+            })
+        }
+    };
+
+    // Read before `check_schema`, which inserts `fallback_dialect` into `$schema`
+    // when absent — reading its return value instead would report every
+    // undeclared schema as the fallback dialect rather than "none declared".
+    let declared: serde_json::Value = match serde_json::from_slice(&body) {
+        Ok(value) => value,
+        Err(err) => {
+            return SchemaStoreOutcome::Invalid(ValidationFailure {
+                path: schema.url,
+                message: err.to_string(),
+            })
+        }
+    };
+
+    if let Err(message) = check_schema(&body, fallback_dialect) {
+        return SchemaStoreOutcome::Invalid(ValidationFailure {
+            path: schema.url,
+            message,
+        });
+    }
+
+    let mut content_hasher = Sha256::new();
+    content_hasher.update(&body);
+    let content_hash = content_hasher.finalize().encode_hex::<String>();
+
+    let dialect = declared
+        .get("$schema")
+        .and_then(|v| v.as_str())
+        .map(str::to_owned);
+    let id = declared
+        .get("$id")
+        .and_then(|v| v.as_str())
+        .map(str::to_owned);
+
+    let mut globs: Vec<Glob> = Vec::new();
+
+    for fm in schema.file_match.iter().filter(|s| s.ends_with(".toml")) {
+        if let Ok(glob) = Glob::new(fm.trim_end_matches(".toml")) {
+            globs.push(glob);
+        }
+    }
+
+    let updated =
+        updated_from_headers.or_else(|| Some(OffsetDateTime::now_utc().format(Format::Rfc3339)));
+
+    SchemaStoreOutcome::Meta(SchemaMeta {
+        title: schema.name,
+        description: schema.description,
+        updated,
+        url: schema.url,
+        url_hash,
+        content_hash,
+        extra: SchemaExtraInfo {
+            authors: vec!["automatically included from https://schemastore.org".into()],
+            dialect,
+            id,
+            patterns: globs
+                .into_iter()
+                .map(|g| {
+                    let mut re = g.regex();
+
+                    re = g
+                        .regex()
+                        .strip_suffix("$")
+                        .unwrap_or(re)
+                        .strip_prefix("(?-u)^")
+                        .unwrap_or(re);
+
+                    if g.regex().contains('*') {
+                        format!(r#"{}\.toml$"#, re)
+                    } else {
+                        format!(r#"^(.*(/|\){}\.toml|{}\.toml)$"#, re, re)
+                    }
+                })
+                .collect(),
+            ..Default::default()
+        },
+    })
 }